@@ -1,4 +1,3 @@
-use anyhow::{bail, Context};
 use clap::{Parser, Subcommand, ValueEnum};
 use usbrelay_rs::usbrelay::{UsbRelayBoard, UsbRelayState};
 
@@ -33,6 +32,18 @@ enum Command {
         /// New serial number
         new_serial_number: String,
     },
+    /// Set state of all relays on a board at once
+    SetAll {
+        /// Relay serial number
+        serial_number: String,
+        /// Desired relay state
+        state: CommandSetStateValue,
+    },
+    /// Re-read live relay states from the board
+    Status {
+        /// Relay serial number
+        serial_number: String,
+    },
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -65,6 +76,11 @@ fn main() -> anyhow::Result<()> {
             serial_number,
             new_serial_number,
         } => update_serial_number(&serial_number, &new_serial_number),
+        Command::SetAll {
+            serial_number,
+            state,
+        } => set_all_relay_state(&serial_number, state.into()),
+        Command::Status { serial_number } => show_relay_status(&serial_number),
     }
 }
 
@@ -78,26 +94,35 @@ fn list_relays() -> anyhow::Result<()> {
 
 fn set_relay_state(serial_number: &str, index: u8, state: UsbRelayState) -> anyhow::Result<()> {
     log::debug!("Attempt to set {serial_number}:{index} {state}");
-    let mut relays = UsbRelayBoard::find_relays()?
-        .into_iter()
-        .filter(|r| r.serial_number() == serial_number)
-        .collect::<Vec<_>>();
+    let mut relay = find_relay(serial_number)?;
+    log::info!("Setting relay {serial_number}:{index} {state}");
+    relay.set_state(index, state)?;
 
-    if relays.is_empty() {
-        bail!("No such relay")
-    }
+    Ok(())
+}
 
-    if relays.len() > 1 {
-        bail!("More than one relay with {serial_number} connected")
-    }
+fn set_all_relay_state(serial_number: &str, state: UsbRelayState) -> anyhow::Result<()> {
+    log::debug!("Attempt to set all relays on {serial_number} to {state}");
+    let mut relay = find_relay(serial_number)?;
+    log::info!("Setting all relays on {serial_number} to {state}");
+    relay.set_all(state)?;
 
-    let relay = relays.get_mut(0).context("Available relays list empty")?;
-    log::info!("Setting relay {serial_number}:{index} {state}");
-    relay.set_state(index, state)?;
+    Ok(())
+}
+
+fn show_relay_status(serial_number: &str) -> anyhow::Result<()> {
+    log::debug!("Attempt to refresh status of {serial_number}");
+    let mut relay = find_relay(serial_number)?;
+    relay.refresh_state()?;
+    println!("{relay}");
 
     Ok(())
 }
 
+fn find_relay(serial_number: &str) -> anyhow::Result<UsbRelayBoard> {
+    UsbRelayBoard::open_by_serial(serial_number)
+}
+
 impl From<CommandSetStateValue> for UsbRelayState {
     fn from(value: CommandSetStateValue) -> Self {
         match value {
@@ -109,6 +134,9 @@ impl From<CommandSetStateValue> for UsbRelayState {
 
 fn update_serial_number(serial_number: &str, new_serial_number: &str) -> anyhow::Result<()> {
     log::debug!("Attempt to update relay serial number {serial_number} -> {new_serial_number}");
+    let mut relay = find_relay(serial_number)?;
+    log::info!("Updating relay {serial_number} serial number to {new_serial_number}");
+    relay.set_serial_number(new_serial_number)?;
 
     Ok(())
 }
@@ -19,13 +19,16 @@ pub struct UsbRelayBoard {
     hid_device: HidDevice,
     serial_number: String,
     relay_states: Vec<UsbRelayState>,
+    state_bitmap: u8,
 }
 
 #[EnumRepr(type = "u8")]
 enum UsbRelayCommand {
     ReadFeatures = 0x01,
     SetSerialNumber = 0xfa,
+    TurnAllOff = 0xfc,
     TurnOff = 0xfd,
+    TurnAllOn = 0xfe,
     TurnOn = 0xff,
 }
 
@@ -44,19 +47,28 @@ impl UsbRelayBoard {
                 .product_string()
                 .context("Can not read product string")?;
 
-            if !product.starts_with("USBRelay") {
-                bail!("Product {product} unsupported")
-            }
-
-            let relay_count = product.trim_start_matches("USBRelay");
-            let relay_count = relay_count.parse::<usize>()?;
-            if relay_count > 8 {
-                bail!("Up to 8 relays supported");
-            }
-
             let hid_device = relay_info.open_device(&hid_api)?;
             let (serial_number, states) = Self::read_features(&hid_device)?;
 
+            let parsed_count = product
+                .strip_prefix("USBRelay")
+                .and_then(|suffix| suffix.parse::<usize>().ok());
+
+            let relay_count = match parsed_count {
+                Some(count) if (1..=8).contains(&count) => count,
+                Some(count) => {
+                    log::warn!("Product {product} reports {count} relays, clamping to 1..=8");
+                    count.clamp(1, 8)
+                }
+                None => {
+                    log::warn!(
+                        "Product {product} has no parsable USBRelayN channel count, \
+                         defaulting to the max supported width of 8"
+                    );
+                    8
+                }
+            };
+
             let mut relay_states = Vec::new();
             for index in 0..relay_count {
                 let relay_state = states & (0x01 << index);
@@ -72,6 +84,7 @@ impl UsbRelayBoard {
                 hid_device,
                 serial_number,
                 relay_states,
+                state_bitmap: states,
             };
 
             usb_relays.push(usb_relay);
@@ -80,10 +93,38 @@ impl UsbRelayBoard {
         Ok(usb_relays)
     }
 
+    pub fn open_by_serial(serial: &str) -> anyhow::Result<Self> {
+        let mut boards = Self::find_relays()?
+            .into_iter()
+            .filter(|r| r.serial_number() == serial)
+            .collect::<Vec<_>>();
+
+        if boards.is_empty() {
+            bail!("No such relay")
+        }
+
+        if boards.len() > 1 {
+            bail!("More than one relay with {serial} connected")
+        }
+
+        boards.pop().context("Available relays list empty")
+    }
+
+    pub fn open_first() -> anyhow::Result<Self> {
+        Self::find_relays()?
+            .into_iter()
+            .next()
+            .context("No relay found")
+    }
+
     pub fn serial_number(&self) -> &str {
         &self.serial_number
     }
 
+    pub fn channel_count(&self) -> usize {
+        self.relay_states.len()
+    }
+
     fn read_features(hid_device: &HidDevice) -> anyhow::Result<(String, u8)> {
         let mut buf = [0u8; 9];
 
@@ -120,15 +161,96 @@ impl UsbRelayBoard {
         buf[1] = command as u8;
         buf[2] = relay_index + 1;
 
-        let wb = self.hid_device.write(&buf)?;
+        self.write_command(&buf)?;
+
+        self.relay_states[relay_index as usize] = state;
+        self.state_bitmap = Self::encode_state_bitmap(&self.relay_states);
+
+        Ok(())
+    }
+
+    pub fn set_serial_number(&mut self, new: &str) -> anyhow::Result<()> {
+        if new.len() != SERIAL_NUMBER_SIZE || !new.is_ascii() {
+            bail!("New serial number must be exactly {SERIAL_NUMBER_SIZE} ASCII characters")
+        }
+
+        let mut buf = [0u8; 9];
+        buf[1] = UsbRelayCommand::SetSerialNumber as u8;
+        buf[2..2 + SERIAL_NUMBER_SIZE].copy_from_slice(new.as_bytes());
+
+        self.write_command(&buf)?;
+
+        self.serial_number = new.to_string();
+
+        Ok(())
+    }
+
+    fn write_command(&mut self, buf: &[u8; 9]) -> anyhow::Result<()> {
+        let wb = self.hid_device.write(buf)?;
         if wb != buf.len() {
             bail!("Not all bytes has been written to relay")
         }
 
-        self.relay_states[relay_index as usize] = state;
+        Ok(())
+    }
+
+    pub fn set_all(&mut self, state: UsbRelayState) -> anyhow::Result<()> {
+        let mut buf = [0u8; 9];
+        let command = match state {
+            UsbRelayState::On => UsbRelayCommand::TurnAllOn,
+            UsbRelayState::Off => UsbRelayCommand::TurnAllOff,
+        };
+        buf[1] = command as u8;
+
+        self.write_command(&buf)?;
+
+        self.relay_states.fill(state);
+        self.state_bitmap = Self::encode_state_bitmap(&self.relay_states);
 
         Ok(())
     }
+
+    pub fn refresh_state(&mut self) -> anyhow::Result<()> {
+        let (_, states) = Self::read_features(&self.hid_device)?;
+
+        for (index, relay_state) in self.relay_states.iter_mut().enumerate() {
+            *relay_state = if states & (0x01 << index) > 0 {
+                UsbRelayState::On
+            } else {
+                UsbRelayState::Off
+            };
+        }
+        self.state_bitmap = states;
+
+        Ok(())
+    }
+
+    pub fn state(&self, index: u8) -> anyhow::Result<UsbRelayState> {
+        self.relay_states
+            .get(index as usize)
+            .copied()
+            .with_context(|| {
+                format!(
+                    "Invalid relay index {index}, board {} has only {} relays",
+                    self.serial_number,
+                    self.relay_states.len()
+                )
+            })
+    }
+
+    pub fn state_bitmap(&self) -> u8 {
+        self.state_bitmap
+    }
+
+    fn encode_state_bitmap(relay_states: &[UsbRelayState]) -> u8 {
+        relay_states
+            .iter()
+            .enumerate()
+            .fold(0u8, |bitmap, (index, state)| match state {
+                UsbRelayState::On => bitmap | (0x01 << index),
+                UsbRelayState::Off => bitmap,
+            })
+    }
 }
 
 impl fmt::Display for UsbRelayBoard {